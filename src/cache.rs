@@ -0,0 +1,74 @@
+//! Content-addressed cache for processed images, so re-running over an
+//! unchanged source tree turns into a no-op instead of a full re-encode.
+use crate::format::Format;
+use crate::resize::ResizeOp;
+use anyhow::{Context, Result};
+use regex::Regex;
+use std::hash::Hasher;
+use std::path::{Path, PathBuf};
+use twox_hash::XxHash64;
+
+/// Default subdirectory used for cached outputs.
+pub const DEFAULT_CACHE_DIR: &str = "processed_images";
+
+/// Compute the cache path for `(input_path, op, format, quality)`.
+///
+/// The key is a 64-bit XxHash over the source path, its mtime and size (so a
+/// touched-but-unchanged file still misses), the resize op and the output
+/// format/quality, rendered as a 16-hex-char stem plus a 2-hex-char op
+/// discriminator, e.g. `deadbeefcafef00d03.jpg`.
+pub fn cache_path(
+    cache_dir: &Path,
+    input_path: &Path,
+    op: ResizeOp,
+    format: Format,
+    quality: u8,
+) -> Result<PathBuf> {
+    let metadata = std::fs::metadata(input_path)
+        .with_context(|| format!("Failed to stat {}", input_path.display()))?;
+    let mtime = metadata
+        .modified()
+        .context("Failed to read mtime")?
+        .duration_since(std::time::UNIX_EPOCH)
+        .context("mtime predates the epoch")?
+        .as_secs();
+
+    let mut hasher = XxHash64::with_seed(0);
+    hasher.write(input_path.to_string_lossy().as_bytes());
+    hasher.write_u64(mtime);
+    hasher.write_u64(metadata.len());
+    hasher.write(format!("{op:?}").as_bytes());
+    hasher.write_u8(format.discriminant());
+    hasher.write_u8(quality);
+    let hash = hasher.finish();
+
+    let filename = format!(
+        "{:016x}{:02x}.{}",
+        hash,
+        op.discriminant(),
+        format.extension()
+    );
+    Ok(cache_dir.join(filename))
+}
+
+/// Collect cached entries in `cache_dir` whose filename matches `pattern`,
+/// e.g. for pruning stale entries left behind by an old resize op or format.
+pub fn collect_matching(cache_dir: &Path, pattern: &str) -> Result<Vec<PathBuf>> {
+    let re = Regex::new(pattern).with_context(|| format!("Invalid pattern: {pattern}"))?;
+
+    if !cache_dir.exists() {
+        return Ok(Vec::new());
+    }
+
+    let mut matches = Vec::new();
+    for entry in std::fs::read_dir(cache_dir)
+        .with_context(|| format!("Failed to read {}", cache_dir.display()))?
+    {
+        let entry = entry?;
+        let name = entry.file_name();
+        if re.is_match(&name.to_string_lossy()) {
+            matches.push(entry.path());
+        }
+    }
+    Ok(matches)
+}
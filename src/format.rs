@@ -0,0 +1,116 @@
+//! Output format selection and encoding.
+use anyhow::{Context, Result};
+use image::codecs::jpeg::JpegEncoder;
+use image::codecs::png::PngEncoder;
+use image::{DynamicImage, ImageEncoder};
+use std::fs::File;
+use std::io::BufWriter;
+use std::path::{Path, PathBuf};
+
+/// The output image format, chosen explicitly or inferred from the source.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Format {
+    Jpeg,
+    Png,
+    Webp,
+}
+
+impl Format {
+    /// Resolve the `--format` flag against the source path.
+    ///
+    /// `auto` picks JPEG for sources that are already lossy (jpg/jpeg/webp)
+    /// and PNG for lossless sources (png/bmp/tiff/gif), otherwise the
+    /// explicit choice is honored.
+    pub fn from_args(source: &Path, format: &str, quality: u8) -> Result<(Format, u8)> {
+        if !(1..=100).contains(&quality) {
+            return Err(anyhow::anyhow!(
+                "Quality must be between 1 and 100, got {quality}"
+            ));
+        }
+
+        let resolved = match format {
+            "auto" => {
+                let ext = source
+                    .extension()
+                    .and_then(|s| s.to_str())
+                    .unwrap_or("")
+                    .to_lowercase();
+                match ext.as_str() {
+                    "jpg" | "jpeg" | "webp" => Format::Jpeg,
+                    _ => Format::Png,
+                }
+            }
+            "jpeg" | "jpg" => Format::Jpeg,
+            "png" => Format::Png,
+            "webp" => Format::Webp,
+            other => return Err(anyhow::anyhow!("Unknown format '{other}'")),
+        };
+
+        Ok((resolved, quality))
+    }
+
+    /// The file extension this format should be saved with.
+    pub fn extension(&self) -> &'static str {
+        match self {
+            Format::Jpeg => "jpg",
+            Format::Png => "png",
+            Format::Webp => "webp",
+        }
+    }
+
+    /// Swap `path`'s extension for the one matching this format.
+    pub fn with_extension(&self, path: &Path) -> PathBuf {
+        path.with_extension(self.extension())
+    }
+
+    /// A short, stable discriminant used to namespace cache filenames.
+    pub fn discriminant(&self) -> u8 {
+        match self {
+            Format::Jpeg => 0,
+            Format::Png => 1,
+            Format::Webp => 2,
+        }
+    }
+}
+
+/// Encode `img` to `output_path` (whose extension has already been set via
+/// [`Format::with_extension`]) using the given format and quality.
+pub fn encode(img: &DynamicImage, format: Format, quality: u8, output_path: &Path) -> Result<()> {
+    match format {
+        Format::Jpeg => {
+            // The JPEG encoder only accepts Rgb8, so flatten away any alpha
+            // channel (and any other source color type) first.
+            let rgb = img.to_rgb8();
+            let file = File::create(output_path)
+                .with_context(|| format!("Failed to create {}", output_path.display()))?;
+            let mut writer = BufWriter::new(file);
+            JpegEncoder::new_with_quality(&mut writer, quality)
+                .write_image(
+                    rgb.as_raw(),
+                    rgb.width(),
+                    rgb.height(),
+                    image::ColorType::Rgb8.into(),
+                )
+                .with_context(|| format!("Failed to encode JPEG: {}", output_path.display()))?;
+        }
+        Format::Png => {
+            let file = File::create(output_path)
+                .with_context(|| format!("Failed to create {}", output_path.display()))?;
+            let writer = BufWriter::new(file);
+            PngEncoder::new(writer)
+                .write_image(img.as_bytes(), img.width(), img.height(), img.color().into())
+                .with_context(|| format!("Failed to encode PNG: {}", output_path.display()))?;
+        }
+        Format::Webp => {
+            // `webp::Encoder` only accepts Rgb8/Rgba8, so normalize any
+            // source color type (grayscale, 16-bit, ...) to Rgba8 first.
+            let rgba = img.to_rgba8();
+            let encoded = webp::Encoder::from_rgba(&rgba, rgba.width(), rgba.height())
+                .encode(quality as f32);
+            std::fs::write(output_path, &*encoded)
+                .with_context(|| format!("Failed to write {}", output_path.display()))?;
+        }
+    }
+
+    Ok(())
+}
@@ -3,42 +3,153 @@
 //!
 use anyhow::{Context, Result};
 use argh::FromArgs;
-use image::imageops::FilterType;
+use rayon::prelude::*;
 use std::path::{Path, PathBuf};
 use walkdir::WalkDir;
 
+mod cache;
+mod format;
+mod meta;
+mod resize;
+mod stats;
+use format::Format;
+use resize::{apply_resize_op, parse_resize_op, ResizeOp};
+
 /// Simple image resizer
 #[derive(FromArgs)]
 struct Args {
+    #[argh(subcommand)]
+    command: Command,
+}
+
+#[derive(FromArgs)]
+#[argh(subcommand)]
+enum Command {
+    Resize(ResizeArgs),
+    Stats(StatsArgs),
+    PruneCache(PruneCacheArgs),
+}
+
+/// resize images
+#[derive(FromArgs)]
+#[argh(subcommand, name = "resize")]
+struct ResizeArgs {
     /// input image path or directory
     #[argh(positional)]
     input: Vec<PathBuf>,
 
-    /// resize dimensions (e.g., "500x400" or "20%")
+    /// resize dimensions (e.g., "500x400", "500x", "x400" or "20%")
     #[argh(option)]
     resize: String,
 
+    /// resize mode: "fit" (default, preserve aspect within the box), "fill"
+    /// (cover the box and center-crop) or "scale" (exact, ignore aspect)
+    #[argh(option, default = "String::from(\"fit\")")]
+    mode: String,
+
     /// output path (optional)
     #[argh(option, short = 'o')]
     output: Option<PathBuf>,
 
+    /// output format: "auto" (default, inferred from source), "jpeg", "png" or "webp"
+    #[argh(option, default = "String::from(\"auto\")")]
+    format: String,
+
+    /// output quality 1-100 for lossy formats (default 85)
+    #[argh(option, default = "85")]
+    quality: u8,
+
     /// overwrite files without prompting
     #[argh(switch, short = 'f')]
     force: bool,
+
+    /// number of worker threads to use (default: rayon's own heuristic, usually one per core)
+    #[argh(option)]
+    jobs: Option<usize>,
+
+    /// cache outputs in a content-addressed `processed_images/` subdir and skip re-encoding unchanged sources
+    #[argh(switch)]
+    cache: bool,
+
+    /// directory to use for the cache (implies --cache), default "processed_images"
+    #[argh(option)]
+    cache_dir: Option<PathBuf>,
+
+    /// skip files whose longest side is not greater than N pixels
+    #[argh(option)]
+    min_dimension: Option<u32>,
+
+    /// skip files that aren't larger than WxH in both dimensions
+    #[argh(option)]
+    only_larger_than: Option<String>,
+}
+
+/// summarize an image directory (count, size, format and dimension breakdown)
+#[derive(FromArgs)]
+#[argh(subcommand, name = "stats")]
+struct StatsArgs {
+    /// directory to scan
+    #[argh(positional)]
+    srcfolder: PathBuf,
+}
+
+/// delete stale entries from a content-addressed cache directory
+#[derive(FromArgs)]
+#[argh(subcommand, name = "prune-cache")]
+struct PruneCacheArgs {
+    /// cache directory to prune
+    #[argh(positional)]
+    cache_dir: PathBuf,
+
+    /// only delete entries whose filename matches this regex (default: all entries)
+    #[argh(option, default = "String::from(\".*\")")]
+    pattern: String,
 }
 
 fn main() -> Result<()> {
     let args: Args = argh::from_env();
 
+    match args.command {
+        Command::Resize(resize_args) => run_resize(resize_args),
+        Command::Stats(stats_args) => stats::run(&stats_args.srcfolder),
+        Command::PruneCache(prune_args) => run_prune_cache(prune_args),
+    }
+}
+
+fn run_prune_cache(args: PruneCacheArgs) -> Result<()> {
+    let stale = cache::collect_matching(&args.cache_dir, &args.pattern)?;
+
+    for path in &stale {
+        std::fs::remove_file(path)
+            .with_context(|| format!("Failed to remove {}", path.display()))?;
+        println!("Pruned: {}", path.display());
+    }
+
+    println!("Pruned {} cache entries", stale.len());
+    Ok(())
+}
+
+fn run_resize(args: ResizeArgs) -> Result<()> {
     if args.input.is_empty() {
         return Err(anyhow::anyhow!("No input files specified"));
     }
 
+    let use_cache = args.cache || args.cache_dir.is_some();
+    let cache_dir = args
+        .cache_dir
+        .clone()
+        .unwrap_or_else(|| PathBuf::from(cache::DEFAULT_CACHE_DIR));
+    if use_cache {
+        std::fs::create_dir_all(&cache_dir)
+            .with_context(|| format!("Failed to create cache dir {}", cache_dir.display()))?;
+    }
+
     let mut image_files = Vec::new();
     for input in &args.input {
         if input.is_dir() {
             for entry in WalkDir::new(input)
                 .into_iter()
+                .filter_entry(|e| !is_within(e.path(), &cache_dir))
                 .filter_map(|e| e.ok())
                 .filter(|e| e.file_type().is_file())
             {
@@ -49,7 +160,7 @@ fn main() -> Result<()> {
         } else if input.to_str().unwrap_or("").contains('*') {
             for entry in glob::glob(input.to_str().unwrap())? {
                 let path = entry?;
-                if is_image_file(&path) {
+                if is_image_file(&path) && !is_within(&path, &cache_dir) {
                     image_files.push(path);
                 }
             }
@@ -62,31 +173,110 @@ fn main() -> Result<()> {
         return Err(anyhow::anyhow!("No valid image files found"));
     }
 
+    if let Some(jobs) = args.jobs {
+        rayon::ThreadPoolBuilder::new()
+            .num_threads(jobs)
+            .build_global()
+            .context("Failed to configure thread pool")?;
+    }
+
+    // Resolve resize ops, output paths and overwrite decisions up front:
+    // `confirm_overwrite` reads from stdin, which can't run concurrently once
+    // we fan out below.
+    let only_larger_than = args
+        .only_larger_than
+        .as_deref()
+        .map(parse_wh)
+        .transpose()?;
+
+    let mut jobs = Vec::new();
     for input_path in image_files {
-        let output_path = match &args.output {
-            Some(o) if o.is_dir() => o.join(input_path.file_name().unwrap()),
-            Some(o) => o.clone(),
-            None => input_path.clone(),
+        let image_meta = meta::probe(&input_path)?;
+        let (src_w, src_h) = image_meta.size;
+
+        if let Some(min) = args.min_dimension {
+            if src_w.max(src_h) <= min {
+                println!("Skipped (already small): {}", input_path.display());
+                continue;
+            }
+        }
+        if let Some((tw, th)) = only_larger_than {
+            if src_w <= tw && src_h <= th {
+                println!("Skipped (already small): {}", input_path.display());
+                continue;
+            }
+        }
+
+        let op = parse_resize_op(&args.resize, &args.mode, src_w, src_h)?;
+        let (chosen_format, quality) = Format::from_args(&input_path, &args.format, args.quality)?;
+
+        let output_path = if use_cache {
+            cache::cache_path(&cache_dir, &input_path, op, chosen_format, quality)?
+        } else {
+            let raw_output_path = match &args.output {
+                Some(o) if o.is_dir() => o.join(input_path.file_name().unwrap()),
+                Some(o) => o.clone(),
+                None => input_path.clone(),
+            };
+            chosen_format.with_extension(&raw_output_path)
         };
 
-        if output_path.exists() && !args.force {
-            if !confirm_overwrite(&output_path)? {
+        if output_path.exists() {
+            if use_cache {
+                println!(
+                    "Cached: {} -> {}",
+                    input_path.display(),
+                    output_path.display()
+                );
+                continue;
+            }
+            if !args.force && !confirm_overwrite(&output_path)? {
                 continue;
             }
         }
 
-        resize_image(&input_path, &output_path, &args.resize)?;
-        println!(
-            "Processed: {} -> {}",
-            input_path.display(),
-            output_path.display()
-        );
+        jobs.push((input_path, output_path, op, chosen_format, quality));
     }
 
+    jobs.par_iter()
+        .try_for_each(|(input_path, output_path, op, chosen_format, quality)| -> Result<()> {
+            resize_image(input_path, output_path, *op, *chosen_format, *quality)?;
+            println!(
+                "Processed: {} -> {}",
+                input_path.display(),
+                output_path.display()
+            );
+            Ok(())
+        })?;
+
     Ok(())
 }
 
-fn is_image_file(path: &Path) -> bool {
+/// Whether `path` is (or is inside) `ancestor`, comparing canonicalized
+/// paths so a cache dir isn't walked back in as input on a later run.
+fn is_within(path: &Path, ancestor: &Path) -> bool {
+    match (path.canonicalize(), ancestor.canonicalize()) {
+        (Ok(p), Ok(a)) => p.starts_with(a),
+        _ => false,
+    }
+}
+
+/// Parse a `WxH` threshold, as used by `--only-larger-than`.
+fn parse_wh(arg: &str) -> Result<(u32, u32)> {
+    let dims: Vec<&str> = arg.split('x').collect();
+    if dims.len() != 2 {
+        return Err(anyhow::anyhow!("--only-larger-than must be in 'WxH' form"));
+    }
+    let width = dims[0]
+        .parse()
+        .with_context(|| format!("Invalid width: {}", dims[0]))?;
+    let height = dims[1]
+        .parse()
+        .with_context(|| format!("Invalid height: {}", dims[1]))?;
+    Ok((width, height))
+}
+
+pub(crate) fn is_image_file(path: &Path) -> bool {
     let ext = path
         .extension()
         .and_then(|s| s.to_str())
@@ -108,45 +298,19 @@ fn confirm_overwrite(path: &Path) -> Result<bool> {
     Ok(input.trim().eq_ignore_ascii_case("y"))
 }
 
-fn resize_image(input_path: &Path, output_path: &Path, resize_arg: &str) -> Result<()> {
-    // Load image
+fn resize_image(
+    input_path: &Path,
+    output_path: &Path,
+    op: ResizeOp,
+    output_format: Format,
+    quality: u8,
+) -> Result<()> {
     let img = image::open(input_path)
         .with_context(|| format!("Failed to open image: {}", input_path.display()))?;
 
-    // Parse resize argument
-    let (width, height) = if resize_arg.ends_with('%') {
-        // Percentage scaling
-        let percent = resize_arg
-            .trim_end_matches('%')
-            .parse::<f32>()
-            .with_context(|| format!("Invalid percentage: {}", resize_arg))?
-            / 100.0;
-        let (w, h) = (img.width() as f32 * percent, img.height() as f32 * percent);
-        (w.round() as u32, h.round() as u32)
-    } else {
-        // Exact dimensions (format "WxH")
-        let dims: Vec<&str> = resize_arg.split('x').collect();
-        if dims.len() != 2 {
-            return Err(anyhow::anyhow!(
-                "Resize format must be either 'WxH' or 'N%'"
-            ));
-        }
-        (
-            dims[0]
-                .parse()
-                .with_context(|| format!("Invalid width: {}", dims[0]))?,
-            dims[1]
-                .parse()
-                .with_context(|| format!("Invalid height: {}", dims[1]))?,
-        )
-    };
-
-    // Resize image (Lanczos3 is high reasonably high quality)
-    let resized = img.resize(width, height, FilterType::Lanczos3);
-
-    resized
-        .save(output_path)
-        .with_context(|| format!("Failed to save image: {}", output_path.display()))?;
+    let resized = apply_resize_op(&img, op);
+
+    format::encode(&resized, output_format, quality, output_path)?;
 
     Ok(())
 }
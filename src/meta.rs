@@ -0,0 +1,28 @@
+//! Cheap image metadata: dimensions and format read from the header only,
+//! without decoding pixel data. Shared by resize filtering, `stats` and the
+//! cache key.
+use anyhow::Context;
+use anyhow::Result;
+use image::ImageFormat;
+use std::path::Path;
+
+/// Dimensions and format of an image, read without decoding pixels.
+#[derive(Debug, Clone, Copy)]
+pub struct ImageMeta {
+    pub size: (u32, u32),
+    pub format: Option<ImageFormat>,
+}
+
+/// Read `path`'s header to get its dimensions and format.
+pub fn probe(path: &Path) -> Result<ImageMeta> {
+    let reader = image::io::Reader::open(path)
+        .with_context(|| format!("Failed to open image: {}", path.display()))?
+        .with_guessed_format()
+        .with_context(|| format!("Failed to detect format: {}", path.display()))?;
+    let format = reader.format();
+    let size = reader
+        .into_dimensions()
+        .with_context(|| format!("Failed to read dimensions: {}", path.display()))?;
+
+    Ok(ImageMeta { size, format })
+}
@@ -0,0 +1,110 @@
+//! Resize geometry: how a source image maps onto its target dimensions.
+use anyhow::{Context, Result};
+use image::imageops::FilterType;
+use image::DynamicImage;
+
+/// How an image should be fitted into (or scaled to) its target dimensions.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ResizeOp {
+    /// Exact `WxH`, aspect ratio ignored.
+    Scale(u32, u32),
+    /// Fixed width, height derived from the source aspect ratio.
+    FitWidth(u32),
+    /// Fixed height, width derived from the source aspect ratio.
+    FitHeight(u32),
+    /// Largest image that fits inside `WxH`, aspect ratio preserved, never upscaled beyond the box.
+    Fit(u32, u32),
+    /// Scale to cover `WxH`, then center-crop to exactly `WxH`.
+    Fill(u32, u32),
+}
+
+impl ResizeOp {
+    /// A short, stable discriminant used to namespace cache filenames.
+    pub fn discriminant(&self) -> u8 {
+        match self {
+            ResizeOp::Scale(..) => 0,
+            ResizeOp::FitWidth(..) => 1,
+            ResizeOp::FitHeight(..) => 2,
+            ResizeOp::Fit(..) => 3,
+            ResizeOp::Fill(..) => 4,
+        }
+    }
+}
+
+/// Parse the `--resize` argument and `--mode` flag into a [`ResizeOp`].
+///
+/// `src_w`/`src_h` are needed to turn a percentage into absolute pixels.
+pub fn parse_resize_op(resize_arg: &str, mode: &str, src_w: u32, src_h: u32) -> Result<ResizeOp> {
+    if let Some(pct) = resize_arg.strip_suffix('%') {
+        let percent = pct
+            .parse::<f32>()
+            .with_context(|| format!("Invalid percentage: {}", resize_arg))?
+            / 100.0;
+        let w = (src_w as f32 * percent).round() as u32;
+        let h = (src_h as f32 * percent).round() as u32;
+        return Ok(ResizeOp::Scale(w, h));
+    }
+
+    if let Some(w) = resize_arg.strip_suffix('x') {
+        let width = w
+            .parse()
+            .with_context(|| format!("Invalid width: {}", w))?;
+        return Ok(ResizeOp::FitWidth(width));
+    }
+
+    if let Some(h) = resize_arg.strip_prefix('x') {
+        let height = h
+            .parse()
+            .with_context(|| format!("Invalid height: {}", h))?;
+        return Ok(ResizeOp::FitHeight(height));
+    }
+
+    let dims: Vec<&str> = resize_arg.split('x').collect();
+    if dims.len() != 2 {
+        return Err(anyhow::anyhow!(
+            "Resize format must be 'WxH', '500x', 'x400' or 'N%'"
+        ));
+    }
+    let width = dims[0]
+        .parse()
+        .with_context(|| format!("Invalid width: {}", dims[0]))?;
+    let height = dims[1]
+        .parse()
+        .with_context(|| format!("Invalid height: {}", dims[1]))?;
+
+    match mode {
+        "scale" => Ok(ResizeOp::Scale(width, height)),
+        "fill" => Ok(ResizeOp::Fill(width, height)),
+        "fit" => Ok(ResizeOp::Fit(width, height)),
+        other => Err(anyhow::anyhow!(
+            "Unknown resize mode '{other}', expected 'fit', 'fill' or 'scale'"
+        )),
+    }
+}
+
+/// Apply a [`ResizeOp`] to `img` (Lanczos3 is reasonably high quality).
+pub fn apply_resize_op(img: &DynamicImage, op: ResizeOp) -> DynamicImage {
+    match op {
+        ResizeOp::Scale(w, h) => img.resize_exact(w, h, FilterType::Lanczos3),
+        ResizeOp::FitWidth(w) => {
+            let h = (w as f32 * img.height() as f32 / img.width() as f32).round() as u32;
+            img.resize_exact(w, h, FilterType::Lanczos3)
+        }
+        ResizeOp::FitHeight(h) => {
+            let w = (h as f32 * img.width() as f32 / img.height() as f32).round() as u32;
+            img.resize_exact(w, h, FilterType::Lanczos3)
+        }
+        ResizeOp::Fit(w, h) => img.resize(w, h, FilterType::Lanczos3),
+        ResizeOp::Fill(w, h) => {
+            let scale_w = w as f32 / img.width() as f32;
+            let scale_h = h as f32 / img.height() as f32;
+            let scale = scale_w.max(scale_h);
+            let rw = (img.width() as f32 * scale).round() as u32;
+            let rh = (img.height() as f32 * scale).round() as u32;
+            let resized = img.resize_exact(rw, rh, FilterType::Lanczos3);
+            let x = (rw.saturating_sub(w)) / 2;
+            let y = (rh.saturating_sub(h)) / 2;
+            resized.crop_imm(x, y, w, h)
+        }
+    }
+}
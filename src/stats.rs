@@ -0,0 +1,76 @@
+//! `stats` subcommand: summarize an image directory without decoding pixels.
+use crate::is_image_file;
+use crate::meta;
+use anyhow::Context;
+use anyhow::Result;
+use std::collections::HashMap;
+use std::path::Path;
+use walkdir::WalkDir;
+
+#[derive(Default)]
+struct FormatStats {
+    count: usize,
+    bytes: u64,
+}
+
+/// Walk `srcfolder` and print aggregate counts, on-disk size, and a
+/// breakdown by format and by dimensions, reading only image headers.
+pub fn run(srcfolder: &Path) -> Result<()> {
+    let mut total_count = 0usize;
+    let mut total_bytes = 0u64;
+    let mut by_format: HashMap<String, FormatStats> = HashMap::new();
+    let mut by_dimensions: HashMap<(u32, u32), usize> = HashMap::new();
+
+    for entry in WalkDir::new(srcfolder)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .filter(|e| e.file_type().is_file())
+    {
+        let path = entry.path();
+        if !is_image_file(path) {
+            continue;
+        }
+
+        let metadata = entry
+            .metadata()
+            .with_context(|| format!("Failed to stat {}", path.display()))?;
+
+        let image_meta = meta::probe(path)?;
+        let format_name = image_meta
+            .format
+            .map(|f| format!("{f:?}"))
+            .unwrap_or_else(|| "unknown".to_string());
+        let (width, height) = image_meta.size;
+
+        total_count += 1;
+        total_bytes += metadata.len();
+
+        let format_stats = by_format.entry(format_name).or_default();
+        format_stats.count += 1;
+        format_stats.bytes += metadata.len();
+
+        *by_dimensions.entry((width, height)).or_insert(0) += 1;
+    }
+
+    println!("Total images: {total_count}");
+    println!("Total size:   {total_bytes} bytes");
+
+    println!("\nBy format:");
+    let mut formats: Vec<_> = by_format.into_iter().collect();
+    formats.sort_by(|a, b| b.1.count.cmp(&a.1.count));
+    for (format_name, stats) in formats {
+        println!(
+            "  {:<8} {:>6} files  {:>12} bytes",
+            format_name, stats.count, stats.bytes
+        );
+    }
+
+    println!("\nBy dimensions:");
+    let mut dimensions: Vec<_> = by_dimensions.into_iter().collect();
+    dimensions.sort_by(|a, b| b.1.cmp(&a.1));
+    for ((width, height), count) in dimensions {
+        println!("  {:>5}x{:<5} {:>6} files", width, height, count);
+    }
+
+    Ok(())
+}